@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// A problem found while assembling a source line, the inverse of the decode
+/// errors the disassembler never has to report.
+#[derive(Debug)]
+pub enum AssembleError {
+    UnknownMnemonic { line: usize, mnemonic: String },
+    InvalidRegister { line: usize, token: String },
+    InvalidImmediate { line: usize, token: String },
+    ImmediateOutOfRange { line: usize, value: i64 },
+    UndefinedLabel { line: usize, label: String },
+}
+
+impl fmt::Display for AssembleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AssembleError::UnknownMnemonic { line, mnemonic } => {
+                write!(f, "line {}: unknown mnemonic `{}`", line, mnemonic)
+            }
+            AssembleError::InvalidRegister { line, token } => {
+                write!(f, "line {}: invalid register `{}`", line, token)
+            }
+            AssembleError::InvalidImmediate { line, token } => {
+                write!(f, "line {}: invalid immediate `{}`", line, token)
+            }
+            AssembleError::ImmediateOutOfRange { line, value } => {
+                write!(f, "line {}: immediate {} does not fit in 25 bits", line, value)
+            }
+            AssembleError::UndefinedLabel { line, label } => {
+                write!(f, "line {}: undefined label `{}`", line, label)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AssembleError {}
+
+/// Assemble UM assembly text into the `Vec<u32>` layout `run` expects for array 0.
+///
+/// Mnemonics: `cmove read write add mul div nand halt alloc free out in load orth`,
+/// registers `r0..r7`, and `orth rX <imm>` for the 25-bit immediate load. A line
+/// `name:` defines a label at the current word offset, and `.word name` emits that
+/// offset as a literal (for building `load`-style jump tables).
+pub fn assemble(source: &str) -> Result<Vec<u32>, AssembleError> {
+    let lines: Vec<&str> = source.lines().collect();
+    let labels = collect_labels(&lines);
+
+    let mut words = Vec::new();
+    for (index, raw_line) in lines.iter().enumerate() {
+        let line_number = index + 1;
+        let line = strip_comment(raw_line).trim();
+        if line.is_empty() || line.ends_with(':') {
+            continue;
+        }
+
+        let mut tokens = line.split_whitespace();
+        let mnemonic = tokens.next().unwrap();
+        let args: Vec<&str> = tokens.collect();
+
+        let word = match mnemonic {
+            ".word" => {
+                let label = *args.first().ok_or_else(|| AssembleError::UndefinedLabel {
+                    line: line_number,
+                    label: String::new(),
+                })?;
+                *labels
+                    .get(label)
+                    .ok_or_else(|| AssembleError::UndefinedLabel {
+                        line: line_number,
+                        label: label.to_string(),
+                    })? as u32
+            }
+            "cmove" => encode_abc(0, &args, line_number)?,
+            "read" => encode_abc(1, &args, line_number)?,
+            "write" => encode_abc(2, &args, line_number)?,
+            "add" => encode_abc(3, &args, line_number)?,
+            "mul" => encode_abc(4, &args, line_number)?,
+            "div" => encode_abc(5, &args, line_number)?,
+            "nand" => encode_abc(6, &args, line_number)?,
+            "halt" => 7 << 28,
+            "alloc" => encode_bc(8, &args, line_number)?,
+            "free" => encode_c(9, &args, line_number)?,
+            "out" => encode_c(10, &args, line_number)?,
+            "in" => encode_c(11, &args, line_number)?,
+            "load" => encode_bc(12, &args, line_number)?,
+            "orth" => encode_immediate(&args, line_number)?,
+            other => {
+                return Err(AssembleError::UnknownMnemonic {
+                    line: line_number,
+                    mnemonic: other.to_string(),
+                })
+            }
+        };
+
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+fn collect_labels(lines: &[&str]) -> HashMap<String, usize> {
+    let mut labels = HashMap::new();
+    let mut offset = 0usize;
+
+    for raw_line in lines {
+        let line = strip_comment(raw_line).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(name) = line.strip_suffix(':') {
+            labels.insert(name.trim().to_string(), offset);
+        } else {
+            offset += 1;
+        }
+    }
+
+    labels
+}
+
+fn strip_comment(line: &str) -> &str {
+    match line.find(';') {
+        Some(index) => &line[..index],
+        None => line,
+    }
+}
+
+fn parse_register(token: &str, line: usize) -> Result<usize, AssembleError> {
+    let register = token
+        .strip_prefix('r')
+        .and_then(|digits| digits.parse::<usize>().ok())
+        .filter(|&r| r < 8);
+
+    register.ok_or_else(|| AssembleError::InvalidRegister {
+        line,
+        token: token.to_string(),
+    })
+}
+
+fn parse_immediate(token: &str, line: usize) -> Result<u32, AssembleError> {
+    let value = if let Some(hex) = token.strip_prefix("0x") {
+        i64::from_str_radix(hex, 16)
+    } else {
+        token.parse::<i64>()
+    }
+    .map_err(|_| AssembleError::InvalidImmediate {
+        line,
+        token: token.to_string(),
+    })?;
+
+    if !(0..(1 << 25)).contains(&value) {
+        return Err(AssembleError::ImmediateOutOfRange { line, value });
+    }
+
+    Ok(value as u32)
+}
+
+fn encode_abc(opcode: u32, args: &[&str], line: usize) -> Result<u32, AssembleError> {
+    let a = parse_register(args.first().copied().unwrap_or(""), line)?;
+    let b = parse_register(args.get(1).copied().unwrap_or(""), line)?;
+    let c = parse_register(args.get(2).copied().unwrap_or(""), line)?;
+    Ok(opcode << 28 | (a as u32) << 6 | (b as u32) << 3 | c as u32)
+}
+
+fn encode_bc(opcode: u32, args: &[&str], line: usize) -> Result<u32, AssembleError> {
+    let b = parse_register(args.first().copied().unwrap_or(""), line)?;
+    let c = parse_register(args.get(1).copied().unwrap_or(""), line)?;
+    Ok(opcode << 28 | (b as u32) << 3 | c as u32)
+}
+
+fn encode_c(opcode: u32, args: &[&str], line: usize) -> Result<u32, AssembleError> {
+    let c = parse_register(args.first().copied().unwrap_or(""), line)?;
+    Ok(opcode << 28 | c as u32)
+}
+
+fn encode_immediate(args: &[&str], line: usize) -> Result<u32, AssembleError> {
+    let a = parse_register(args.first().copied().unwrap_or(""), line)?;
+    let value = parse_immediate(args.get(1).copied().unwrap_or(""), line)?;
+    Ok(13 << 28 | (a as u32) << 25 | value)
+}
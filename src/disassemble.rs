@@ -0,0 +1,31 @@
+use um::Operator;
+
+/// Render a loaded program array as addressable UM assembly, one line per word.
+pub fn disassemble(program: &[u32]) -> String {
+    program
+        .iter()
+        .enumerate()
+        .map(|(offset, &word)| format!("{:>8}: {}", offset, disassemble_instruction(word)))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn disassemble_instruction(word: u32) -> String {
+    match Operator::from(word) {
+        Operator::CondMove(a, b, c) => format!("cmove r{} r{} r{}", a, b, c),
+        Operator::Read(a, b, c) => format!("read r{} r{} r{}", a, b, c),
+        Operator::Write(a, b, c) => format!("write r{} r{} r{}", a, b, c),
+        Operator::Add(a, b, c) => format!("add r{} r{} r{}", a, b, c),
+        Operator::Mul(a, b, c) => format!("mul r{} r{} r{}", a, b, c),
+        Operator::Div(a, b, c) => format!("div r{} r{} r{}", a, b, c),
+        Operator::NotAnd(a, b, c) => format!("nand r{} r{} r{}", a, b, c),
+        Operator::Halt => "halt".to_string(),
+        Operator::Alloc(b, c) => format!("alloc r{} r{}", b, c),
+        Operator::Dealloc(c) => format!("free r{}", c),
+        Operator::Out(c) => format!("out r{}", c),
+        Operator::In(c) => format!("in r{}", c),
+        Operator::Load(b, c) => format!("load r{} r{}", b, c),
+        Operator::Immediate(a, value) => format!("orth r{} 0x{:x}", a, value),
+        Operator::Unsupported(opcode) => format!("; unsupported opcode {}", opcode),
+    }
+}
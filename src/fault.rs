@@ -0,0 +1,73 @@
+use core::fmt;
+
+/// One of the UM spec's "Failure states" - a clean fault instead of a panic or
+/// an silent `break` out of the run loop.
+#[derive(Debug)]
+pub enum MachineFault {
+    /// `Read`/`Write`/`Load`/`Dealloc` referenced an array id that is not
+    /// currently allocated (never allocated, or already freed), or `Dealloc`
+    /// was asked to free array 0.
+    InvalidArray { instruction_pointer: usize, array: u32 },
+    /// `Read`/`Write` referenced an offset past the end of an allocated array.
+    OffsetOutOfBounds {
+        instruction_pointer: usize,
+        array: u32,
+        offset: u32,
+        length: usize,
+    },
+    /// `Div` was asked to divide by zero.
+    DivisionByZero { instruction_pointer: usize, dividend: u32 },
+    /// The instruction pointer fell outside array 0, either as the target of a
+    /// `Load` or by running past the last word of the program.
+    JumpOutOfBounds { instruction_pointer: usize, target: usize },
+    /// The top four bits of an instruction did not name one of the 14 operators.
+    InvalidOpcode { instruction_pointer: usize, opcode: u32 },
+}
+
+impl fmt::Display for MachineFault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MachineFault::InvalidArray {
+                instruction_pointer,
+                array,
+            } => write!(
+                f,
+                "at {}: array {} is not allocated",
+                instruction_pointer, array
+            ),
+            MachineFault::OffsetOutOfBounds {
+                instruction_pointer,
+                array,
+                offset,
+                length,
+            } => write!(
+                f,
+                "at {}: offset {} out of bounds for array {} (len {})",
+                instruction_pointer, offset, array, length
+            ),
+            MachineFault::DivisionByZero {
+                instruction_pointer,
+                dividend,
+            } => write!(
+                f,
+                "at {}: division of {} by zero",
+                instruction_pointer, dividend
+            ),
+            MachineFault::JumpOutOfBounds {
+                instruction_pointer,
+                target,
+            } => write!(
+                f,
+                "at {}: jump to {} falls outside array 0",
+                instruction_pointer, target
+            ),
+            MachineFault::InvalidOpcode {
+                instruction_pointer,
+                opcode,
+            } => write!(f, "at {}: invalid opcode {}", instruction_pointer, opcode),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MachineFault {}
@@ -0,0 +1,47 @@
+//! The byte-at-a-time IO surface `Out`/`In` need, kept separate from `std` so
+//! the engine can embed in a bare-metal or WASM host that provides its own.
+
+/// Source for `In`. `None` signals EOF; the VM maps that to the UM spec's
+/// all-ones register rather than stalling or reading stale data.
+pub trait ByteIn {
+    fn read_byte(&mut self) -> Option<u8>;
+}
+
+/// Sink for `Out`.
+pub trait ByteOut {
+    fn write_byte(&mut self, byte: u8);
+}
+
+#[cfg(feature = "std")]
+mod std_io {
+    use super::{ByteIn, ByteOut};
+    use std::io::{self, Read};
+
+    /// Reads exactly one byte per call out of any boxed `std::io::Read`.
+    pub struct StdIn(io::BufReader<Box<dyn Read>>);
+
+    impl StdIn {
+        pub fn new(reader: Box<dyn Read>) -> Self {
+            StdIn(io::BufReader::new(reader))
+        }
+    }
+
+    impl ByteIn for StdIn {
+        fn read_byte(&mut self) -> Option<u8> {
+            let mut byte = [0u8; 1];
+            self.0.read_exact(&mut byte).ok().map(|()| byte[0])
+        }
+    }
+
+    /// Writes each byte to stdout as a `char`, matching the VM's original `Out`.
+    pub struct StdOut;
+
+    impl ByteOut for StdOut {
+        fn write_byte(&mut self, byte: u8) {
+            print!("{}", byte as char);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+pub use std_io::{StdIn, StdOut};
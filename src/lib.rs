@@ -0,0 +1,307 @@
+//! The reusable UM engine: instruction decoding, the flat memory arena, and
+//! the fault/budget-aware `run` loop. `In`/`Out` go through the `ByteIn`/
+//! `ByteOut` traits in [`io_traits`] instead of talking to `std` directly, so
+//! this core compiles under `#![no_std]` (with `alloc`) for bare-metal or
+//! WASM hosts. The `std` feature, on by default, additionally provides
+//! `StdIn`/`StdOut` backed by `std::io`; the `main` binary's file and stdin
+//! handling lives outside this crate entirely.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+pub mod fault;
+pub mod io_traits;
+pub mod outcome;
+
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+pub use fault::MachineFault;
+pub use io_traits::{ByteIn, ByteOut};
+pub use outcome::RunOutcome;
+
+/// A freed slot is `None`; `Alloc`/`Dealloc` recycle slots through `free_mem`
+/// instead of ever shrinking the arena, so array ids stay stable.
+pub struct UniversalMachine<I: ByteIn, O: ByteOut> {
+    instruction_pointer: usize,
+    registers: [u32; 8],
+    memory: Vec<Option<Box<[u32]>>>,
+    next_mem: usize,
+    free_mem: Vec<usize>,
+    input: I,
+    output: O,
+    instructions_executed: u64,
+    opcode_histogram: [u64; 16],
+}
+
+/// Whether a single decoded instruction fell through to the next one or halted.
+enum Step {
+    Continue,
+    Halted,
+}
+
+impl<I: ByteIn, O: ByteOut> UniversalMachine<I, O> {
+    pub fn new(program: Vec<u32>, input: I, output: O) -> Self {
+        UniversalMachine {
+            instruction_pointer: 0,
+            registers: [0; 8],
+            memory: vec![Some(program.into_boxed_slice())],
+            next_mem: 1,
+            free_mem: Vec::new(),
+            input,
+            output,
+            instructions_executed: 0,
+            opcode_histogram: [0; 16],
+        }
+    }
+
+    pub fn instructions_executed(&self) -> u64 {
+        self.instructions_executed
+    }
+
+    pub fn opcode_histogram(&self) -> &[u64; 16] {
+        &self.opcode_histogram
+    }
+
+    /// Run for at most `budget` instructions. Borrows `self` rather than
+    /// consuming it, so a `BudgetExhausted` outcome can be resumed by calling
+    /// `run` again: `instruction_pointer`, `registers`, `memory` and the
+    /// allocator state all carry over untouched.
+    pub fn run(&mut self, budget: u64) -> RunOutcome {
+        let mut spent = 0u64;
+
+        loop {
+            if spent >= budget {
+                return RunOutcome::BudgetExhausted;
+            }
+
+            match self.step() {
+                Ok(Step::Continue) => {}
+                Ok(Step::Halted) => return RunOutcome::Halted,
+                Err(fault) => return RunOutcome::Fault(fault),
+            }
+
+            spent += 1;
+        }
+    }
+
+    fn step(&mut self) -> Result<Step, MachineFault> {
+        let instruction_pointer = self.instruction_pointer;
+
+        // Array 0 is only ever swapped wholesale by `Load`, so this lookup
+        // always resolves to the program currently being executed.
+        let instruction = *self.memory[0]
+            .as_deref()
+            .expect("array 0 is always allocated")
+            .get(instruction_pointer)
+            .ok_or(MachineFault::JumpOutOfBounds {
+                instruction_pointer,
+                target: instruction_pointer,
+            })?;
+        let operator = Operator::from(instruction);
+        self.instruction_pointer = instruction_pointer + 1;
+        self.instructions_executed += 1;
+        self.opcode_histogram[(instruction >> 28) as usize] += 1;
+
+        match operator {
+            Operator::CondMove(a, b, c) => {
+                if self.registers[c] != 0 {
+                    self.registers[a] = self.registers[b]
+                }
+            }
+            Operator::Read(a, b, c) => {
+                let array = self.registers[b];
+                let offset = self.registers[c];
+                let allocation = self
+                    .memory
+                    .get(array as usize)
+                    .and_then(|slot| slot.as_deref())
+                    .ok_or(MachineFault::InvalidArray {
+                        instruction_pointer,
+                        array,
+                    })?;
+                self.registers[a] = *allocation.get(offset as usize).ok_or(
+                    MachineFault::OffsetOutOfBounds {
+                        instruction_pointer,
+                        array,
+                        offset,
+                        length: allocation.len(),
+                    },
+                )?;
+            }
+            Operator::Write(a, b, c) => {
+                let array = self.registers[a];
+                let offset = self.registers[b];
+                let value = self.registers[c];
+                let length = self
+                    .memory
+                    .get(array as usize)
+                    .and_then(|slot| slot.as_deref())
+                    .ok_or(MachineFault::InvalidArray {
+                        instruction_pointer,
+                        array,
+                    })?
+                    .len();
+                let slot = self
+                    .memory
+                    .get_mut(array as usize)
+                    .and_then(|slot| slot.as_deref_mut())
+                    .and_then(|allocation| allocation.get_mut(offset as usize))
+                    .ok_or(MachineFault::OffsetOutOfBounds {
+                        instruction_pointer,
+                        array,
+                        offset,
+                        length,
+                    })?;
+                *slot = value;
+            }
+            Operator::Add(a, b, c) => {
+                self.registers[a] = self.registers[b].wrapping_add(self.registers[c])
+            }
+            Operator::Mul(a, b, c) => {
+                self.registers[a] = self.registers[b].wrapping_mul(self.registers[c])
+            }
+            Operator::Div(a, b, c) => {
+                if self.registers[c] == 0 {
+                    return Err(MachineFault::DivisionByZero {
+                        instruction_pointer,
+                        dividend: self.registers[b],
+                    });
+                }
+                self.registers[a] = self.registers[b].wrapping_div(self.registers[c])
+            }
+            Operator::NotAnd(a, b, c) => {
+                self.registers[a] = !(self.registers[b] & self.registers[c])
+            }
+            Operator::Halt => return Ok(Step::Halted),
+            Operator::Alloc(b, c) => {
+                let mem_size = self.registers[c] as usize;
+                let allocation = vec![0; mem_size].into_boxed_slice();
+
+                let mem_index = match self.free_mem.pop() {
+                    Some(free_index) => free_index,
+                    None => {
+                        let free_index = self.next_mem;
+                        self.next_mem += 1;
+                        free_index
+                    }
+                };
+
+                if mem_index == self.memory.len() {
+                    self.memory.push(Some(allocation));
+                } else {
+                    self.memory[mem_index] = Some(allocation);
+                }
+                self.registers[b] = mem_index as u32;
+            }
+            Operator::Dealloc(c) => {
+                let array = self.registers[c];
+                let target = array as usize;
+                let allocated = array != 0
+                    && self
+                        .memory
+                        .get(target)
+                        .map(|slot| slot.is_some())
+                        .unwrap_or(false);
+                if !allocated {
+                    return Err(MachineFault::InvalidArray {
+                        instruction_pointer,
+                        array,
+                    });
+                }
+                self.memory[target] = None;
+                self.free_mem.push(target);
+            }
+            Operator::Out(c) => self.output.write_byte(self.registers[c as usize] as u8),
+            Operator::In(c) => {
+                self.registers[c] = match self.input.read_byte() {
+                    Some(byte) => byte as u32,
+                    None => 0xFFFFFFFF,
+                };
+            }
+            Operator::Load(b, c) => {
+                let array = self.registers[b];
+                if array != 0 {
+                    let program = self
+                        .memory
+                        .get(array as usize)
+                        .and_then(|slot| slot.as_deref())
+                        .ok_or(MachineFault::InvalidArray {
+                            instruction_pointer,
+                            array,
+                        })?
+                        .to_vec()
+                        .into_boxed_slice();
+                    self.memory[0] = Some(program);
+                }
+                let target = self.registers[c] as usize;
+                if target >= self.memory[0].as_deref().unwrap().len() {
+                    return Err(MachineFault::JumpOutOfBounds {
+                        instruction_pointer,
+                        target,
+                    });
+                }
+                self.instruction_pointer = target;
+            }
+            Operator::Immediate(a, value) => self.registers[a] = value,
+            Operator::Unsupported(opcode) => {
+                return Err(MachineFault::InvalidOpcode {
+                    instruction_pointer,
+                    opcode,
+                })
+            }
+        }
+
+        Ok(Step::Continue)
+    }
+}
+
+#[derive(Debug)]
+pub enum Operator {
+    CondMove(usize, usize, usize),
+    Read(usize, usize, usize),
+    Write(usize, usize, usize),
+    Add(usize, usize, usize),
+    Mul(usize, usize, usize),
+    Div(usize, usize, usize),
+    NotAnd(usize, usize, usize),
+    Halt,
+    Alloc(usize, usize),
+    Dealloc(usize),
+    Out(i8),
+    In(usize),
+    Load(usize, usize),
+    Immediate(usize, u32),
+    Unsupported(u32),
+}
+
+impl From<u32> for Operator {
+    fn from(bit_pattern: u32) -> Self {
+        let a = (bit_pattern >> 6 & 7) as usize;
+        let b = (bit_pattern >> 3 & 7) as usize;
+        let c = (bit_pattern & 7) as usize;
+
+        match bit_pattern >> 28 {
+            0 => Operator::CondMove(a, b, c),
+            1 => Operator::Read(a, b, c),
+            2 => Operator::Write(a, b, c),
+            3 => Operator::Add(a, b, c),
+            4 => Operator::Mul(a, b, c),
+            5 => Operator::Div(a, b, c),
+            6 => Operator::NotAnd(a, b, c),
+            7 => Operator::Halt,
+            8 => Operator::Alloc(b, c),
+            9 => Operator::Dealloc(c),
+            10 => Operator::Out(c.try_into().unwrap()),
+            11 => Operator::In(c),
+            12 => Operator::Load(b, c),
+            13 => Operator::Immediate(
+                (bit_pattern >> 25 & 7) as usize,
+                bit_pattern & 0b1111111111111111111111111, // - 1,
+            ),
+            opcode => Operator::Unsupported(opcode),
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use crate::fault::MachineFault;
+
+/// What a bounded call to `UniversalMachine::run` produced.
+#[derive(Debug)]
+pub enum RunOutcome {
+    /// The program executed `Halt`.
+    Halted,
+    /// The instruction budget ran out before the program halted or faulted;
+    /// call `run` again to resume from where execution left off.
+    BudgetExhausted,
+    /// The program hit one of the UM spec's failure states.
+    Fault(MachineFault),
+}